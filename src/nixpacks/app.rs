@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use glob::glob;
+use jsonc_parser::parse_to_serde_value;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+
+/// A handle onto the application's source directory, providing the file
+/// lookups and structured-config readers every `Provider` is built against.
+pub struct App {
+    pub source: PathBuf,
+}
+
+impl App {
+    pub fn new(source: &str) -> Result<Self> {
+        Ok(Self {
+            source: PathBuf::from(source),
+        })
+    }
+
+    /// Whether `name` (relative to the app source) exists
+    pub fn includes_file(&self, name: &str) -> bool {
+        self.source.join(name).is_file()
+    }
+
+    /// Whether any file matches the glob `pattern`, relative to the app source
+    pub fn has_match(&self, pattern: &str) -> bool {
+        self.find_files(pattern)
+            .map(|matches| !matches.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// All files matching the glob `pattern`, relative to the app source
+    pub fn find_files(&self, pattern: &str) -> Result<Vec<PathBuf>> {
+        // `glob` has no `{a,b}` alternation, so expand one brace group into
+        // separate patterns, matched in the order the alternatives are listed
+        let patterns: Vec<String> = match (pattern.find('{'), pattern.find('}')) {
+            (Some(open), Some(close)) if open < close => pattern[open + 1..close]
+                .split(',')
+                .map(|alt| format!("{}{alt}{}", &pattern[..open], &pattern[close + 1..]))
+                .collect(),
+            _ => vec![pattern.to_string()],
+        };
+
+        let mut matches = Vec::new();
+        for pattern in patterns {
+            let full_pattern = self.source.join(pattern);
+            let full_pattern = full_pattern
+                .to_str()
+                .context("Failed to convert glob pattern to string")?;
+
+            for entry in glob(full_pattern).context("Failed to read glob pattern")? {
+                matches.push(entry?);
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Whether any file matching the glob `pattern` has contents matching `re`
+    pub fn find_match(&self, re: &Regex, pattern: &str) -> Result<bool> {
+        for path in self.find_files(pattern)? {
+            let contents = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            if re.is_match(&contents) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads and deserializes `name` (relative to the app source) as strict JSON
+    pub fn read_json<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let contents = self.read_file_to_string(name)?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {name} as JSON"))
+    }
+
+    /// Reads and deserializes `name` (relative to the app source) as
+    /// JSONC: JSON tolerant of comments and trailing commas, matching how
+    /// Deno itself reads `deno.jsonc`
+    pub fn read_jsonc<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let contents = self.read_file_to_string(name)?;
+        let value = parse_to_serde_value(&contents, &Default::default())
+            .with_context(|| format!("Failed to parse {name} as JSONC"))?
+            .with_context(|| format!("{name} is empty"))?;
+        serde_json::from_value(value).with_context(|| format!("Failed to parse {name} as JSONC"))
+    }
+
+    /// Reads and deserializes `name` (relative to the app source) as TOML
+    pub fn read_toml<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let contents = self.read_file_to_string(name)?;
+        toml::from_str(&contents).with_context(|| format!("Failed to parse {name} as TOML"))
+    }
+
+    fn read_file_to_string(&self, name: &str) -> Result<String> {
+        let path = self.source.join(name);
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    /// Strips the app source prefix from a path under it (such as one returned
+    /// by `find_files`), returning a path relative to the app source
+    pub fn strip_source_path(&self, path: &Path) -> Result<PathBuf> {
+        Ok(path
+            .strip_prefix(&self.source)
+            .with_context(|| format!("Failed to strip source path from {}", path.display()))?
+            .to_path_buf())
+    }
+}
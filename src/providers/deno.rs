@@ -1,4 +1,7 @@
-use std::path::PathBuf;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
+};
 
 use super::Provider;
 use crate::nixpacks::{
@@ -10,19 +13,64 @@ use crate::nixpacks::{
         BuildPlan,
     },
 };
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use path_slash::PathBufExt;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Default, Debug)]
-pub struct DenoTasks {
-    pub start: Option<String>,
+/// A single entry in a deno.json `tasks` table: either a plain command, or an
+/// object form declaring other tasks that must run first
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum DenoTask {
+    Command(String),
+    WithDependencies {
+        command: String,
+        #[serde(default)]
+        dependencies: Vec<String>,
+    },
+}
+
+impl DenoTask {
+    pub fn command(&self) -> &str {
+        match self {
+            DenoTask::Command(command) => command,
+            DenoTask::WithDependencies { command, .. } => command,
+        }
+    }
+
+    pub fn dependencies(&self) -> &[String] {
+        match self {
+            DenoTask::Command(_) => &[],
+            DenoTask::WithDependencies { dependencies, .. } => dependencies,
+        }
+    }
+}
+
+pub type DenoTasks = HashMap<String, DenoTask>;
+
+/// A minimal view of package.json for Deno/npm-interop projects. Unlike
+/// `npm::PackageJson`, `name` is not required: Deno projects routinely keep a
+/// bare `{"scripts": {...}}` (or no package.json at all) alongside deno.json.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct DenoPackageJson {
+    #[serde(default)]
+    scripts: Option<HashMap<String, String>>,
+}
+
+impl DenoPackageJson {
+    fn has_script(&self, name: &str) -> bool {
+        self.scripts
+            .as_ref()
+            .is_some_and(|scripts| scripts.contains_key(name))
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct DenoJson {
     pub tasks: Option<DenoTasks>,
+    #[serde(rename = "importMap")]
+    pub import_map: Option<String>,
 }
 
 pub struct DenoProvider {}
@@ -51,13 +99,58 @@ impl Provider for DenoProvider {
         }
         plan.add_phase(setup);
 
-        if let Some(build_cmd) = DenoProvider::get_build_cmd(app)? {
-            let mut build = Phase::build(Some(build_cmd));
-            build.depends_on_phase("setup");
-            plan.add_phase(build);
+        // Deno resolves a package.json in the project root and merges its
+        // scripts into the task runner, so materialize node_modules for it.
+        let mut last_phase = "setup";
+        if app.includes_file("package.json") {
+            let mut install = Phase::install(Some("deno install".to_string()));
+            install.depends_on_phase("setup");
+
+            let mut only_include_files = vec!["package.json".to_string()];
+            if let Some(lockfile) = DenoProvider::get_lockfile_name(app) {
+                only_include_files.push(lockfile.to_string());
+            }
+            if let Some(import_map) = DenoProvider::get_import_map_path(app)? {
+                only_include_files.push(import_map);
+            }
+            install.only_include_files = Some(only_include_files);
+
+            plan.add_phase(install);
+            last_phase = "install";
+        }
+
+        // Gated behind NIXPACKS_DENO_VENDOR: vendor remote dependencies into
+        // vendor/ so later phases (and the running app) need no network access.
+        let vendor_enabled = env.is_config_variable_truthy("DENO_VENDOR");
+        if vendor_enabled {
+            if let Some(start_file) = DenoProvider::get_start_file(app)? {
+                let mut vendor = Phase::new("vendor");
+                vendor.add_cmd(format!(
+                    "deno vendor{} --no-remote {}",
+                    DenoProvider::get_import_map_flag(app)?,
+                    start_file
+                        .to_slash()
+                        .context("Failed to convert start_file to slash_path")?
+                ));
+                vendor.depends_on_phase(last_phase);
+                plan.add_phase(vendor);
+                last_phase = "vendor";
+            }
+        }
+
+        // Once vendored, `deno vendor` writes its own import map that layers
+        // on top of any configured one; point subsequent commands at it.
+        let import_map_flag = if vendor_enabled {
+            " --import-map=vendor/import_map.json".to_string()
+        } else {
+            DenoProvider::get_import_map_flag(app)?
         };
 
-        if let Some(start_cmd) = DenoProvider::get_start_cmd(app)? {
+        DenoProvider::add_build_phases(app, env, &mut plan, last_phase, &import_map_flag)?;
+
+        if let Some(start_cmd) =
+            DenoProvider::get_start_cmd(app, env, &mut plan, last_phase, &import_map_flag)?
+        {
             let start = StartPhase::new(start_cmd);
             plan.set_start_phase(start);
         }
@@ -67,37 +160,231 @@ impl Provider for DenoProvider {
 }
 
 impl DenoProvider {
-    fn get_build_cmd(app: &App) -> Result<Option<String>> {
-        if let Some(start_file) = DenoProvider::get_start_file(app)? {
-            Ok(Some(format!(
-                "deno cache {}",
-                start_file
-                    .to_slash()
-                    .context("Failed to convert start_file to slash_path")?
-            )))
+    /// Adds the phases needed to produce a build, wiring them onto `entry_phase`.
+    ///
+    /// The task to build is `NIXPACKS_DENO_BUILD_TASK` (defaulting to `build`).
+    /// When that task is present in deno.json, one phase is added for it and
+    /// for each of its transitive dependencies (declared via
+    /// `tasks.<name>.dependencies`), each depending on the phases for its
+    /// declared dependencies. If the task was requested explicitly and is
+    /// missing, this errors. Otherwise this falls back to a package.json
+    /// `build` script run through `deno task`, or a plain `deno cache`.
+    fn add_build_phases(
+        app: &App,
+        env: &Environment,
+        plan: &mut BuildPlan,
+        entry_phase: &str,
+        import_map_flag: &str,
+    ) -> Result<()> {
+        let requested_task = env.get_config_variable("DENO_BUILD_TASK");
+        let task_name = requested_task.clone().unwrap_or_else(|| "build".to_string());
+
+        let tasks = DenoProvider::get_deno_tasks(app)?;
+        if let Some(tasks) = &tasks {
+            if tasks.contains_key(&task_name) {
+                DenoProvider::add_task_phases(app, plan, tasks, &task_name, entry_phase)?;
+                return Ok(());
+            }
+        }
+        if requested_task.is_some() {
+            bail!(
+                "No task named \"{task_name}\" in deno.json. Available tasks: {}",
+                DenoProvider::format_task_names(tasks.as_ref())
+            );
+        }
+
+        let build_cmd = if let Some(package_json) = DenoProvider::get_package_json(app)? {
+            if package_json.has_script("build") {
+                Some(format!("deno{} task build", DenoProvider::get_lock_flag(app)))
+            } else {
+                None
+            }
         } else {
-            Ok(None)
+            None
+        };
+
+        let build_cmd = match build_cmd {
+            Some(cmd) => Some(cmd),
+            None => match DenoProvider::get_start_file(app)? {
+                Some(start_file) => Some(format!(
+                    "deno cache{}{} {}",
+                    DenoProvider::get_lock_flag(app),
+                    import_map_flag,
+                    start_file
+                        .to_slash()
+                        .context("Failed to convert start_file to slash_path")?
+                )),
+                None => None,
+            },
+        };
+
+        if let Some(build_cmd) = build_cmd {
+            let mut build = Phase::build(Some(build_cmd));
+            build.depends_on_phase(entry_phase);
+            plan.add_phase(build);
         }
+
+        Ok(())
+    }
+
+    /// Topologically sorts `task_name` and its transitive `dependencies`, adding
+    /// one phase per reachable task (named `deno-task-<name>`, running
+    /// `deno task <name>`) wired together via `depends_on`. Phases with no
+    /// declared dependency depend on `entry_phase` instead. Errors on cycles.
+    fn add_task_phases(
+        app: &App,
+        plan: &mut BuildPlan,
+        tasks: &DenoTasks,
+        task_name: &str,
+        entry_phase: &str,
+    ) -> Result<String> {
+        let order = DenoProvider::topo_sort_task(tasks, task_name)?;
+        DenoProvider::add_phases_for_tasks(app, plan, tasks, &order, entry_phase);
+        Ok(DenoProvider::task_phase_name(task_name))
+    }
+
+    /// Like `add_task_phases`, but adds phases only for `task_name`'s
+    /// transitive dependencies, not for `task_name` itself. Used for the
+    /// `start` task: its dependencies (e.g. a `migrate` step) must run during
+    /// the build, but the start command itself is a runtime command, not a
+    /// build phase.
+    fn add_dependency_phases(
+        app: &App,
+        plan: &mut BuildPlan,
+        tasks: &DenoTasks,
+        task_name: &str,
+        entry_phase: &str,
+    ) -> Result<()> {
+        let mut order = DenoProvider::topo_sort_task(tasks, task_name)?;
+        order.pop();
+        DenoProvider::add_phases_for_tasks(app, plan, tasks, &order, entry_phase);
+        Ok(())
     }
 
-    fn get_start_cmd(app: &App) -> Result<Option<String>> {
-        // First check for a deno.{json,jsonc} and see if we can rip the start command from there
-        if app.includes_file("deno.json") || app.includes_file("deno.jsonc") {
-            let deno_json: DenoJson = app
-                .read_json("deno.json")
-                .or_else(|_| app.read_json("deno.jsonc"))?;
+    fn add_phases_for_tasks(
+        app: &App,
+        plan: &mut BuildPlan,
+        tasks: &DenoTasks,
+        order: &[String],
+        entry_phase: &str,
+    ) {
+        let lock_flag = DenoProvider::get_lock_flag(app);
+
+        for name in order {
+            let task = tasks
+                .get(name)
+                .expect("topo_sort_task only returns names present in tasks");
+
+            let mut phase = Phase::new(DenoProvider::task_phase_name(name).as_str());
+            phase.add_cmd(format!("deno{lock_flag} task {name}"));
 
-            if let Some(tasks) = deno_json.tasks {
-                if let Some(start) = tasks.start {
-                    return Ok(Some(start));
+            if task.dependencies().is_empty() {
+                phase.depends_on_phase(entry_phase);
+            } else {
+                for dep in task.dependencies() {
+                    phase.depends_on_phase(DenoProvider::task_phase_name(dep).as_str());
                 }
             }
+
+            plan.add_phase(phase);
+        }
+    }
+
+    fn task_phase_name(task_name: &str) -> String {
+        format!("deno-task-{task_name}")
+    }
+
+    /// Returns `task_name` and its transitive dependencies in an order where
+    /// every dependency comes before its dependents
+    fn topo_sort_task(tasks: &DenoTasks, task_name: &str) -> Result<Vec<String>> {
+        let mut order = Vec::new();
+        let mut visited = HashSet::new();
+        let mut visiting = VecDeque::new();
+        DenoProvider::visit_task(tasks, task_name, &mut visiting, &mut visited, &mut order)?;
+        Ok(order)
+    }
+
+    fn visit_task(
+        tasks: &DenoTasks,
+        name: &str,
+        visiting: &mut VecDeque<String>,
+        visited: &mut HashSet<String>,
+        order: &mut Vec<String>,
+    ) -> Result<()> {
+        if visited.contains(name) {
+            return Ok(());
+        }
+        if visiting.contains(&name.to_string()) {
+            bail!(
+                "Cycle detected in deno.json task dependencies: {} -> {name}",
+                visiting.iter().cloned().collect::<Vec<_>>().join(" -> ")
+            );
+        }
+
+        let Some(task) = tasks.get(name) else {
+            bail!(
+                "Task \"{name}\" is referenced as a dependency but is not defined in deno.json. Available tasks: {}",
+                DenoProvider::format_task_names(Some(tasks))
+            );
+        };
+
+        visiting.push_back(name.to_string());
+        for dep in task.dependencies() {
+            DenoProvider::visit_task(tasks, dep, visiting, visited, order)?;
+        }
+        visiting.pop_back();
+
+        visited.insert(name.to_string());
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    fn get_start_cmd(
+        app: &App,
+        env: &Environment,
+        plan: &mut BuildPlan,
+        entry_phase: &str,
+        import_map_flag: &str,
+    ) -> Result<Option<String>> {
+        // First check for a deno.{json,jsonc} and see if we can rip the start command from there.
+        // The task name is NIXPACKS_DENO_START_TASK, defaulting to `start`.
+        let requested_task = env.get_config_variable("DENO_START_TASK");
+        let task_name = requested_task.clone().unwrap_or_else(|| "start".to_string());
+
+        let tasks = DenoProvider::get_deno_tasks(app)?;
+        if let Some(tasks) = &tasks {
+            if let Some(start) = tasks.get(&task_name) {
+                // The start task itself isn't run as a build phase (it's
+                // typically a long-running server), but its dependencies are
+                // real build steps and must still run before the app starts.
+                DenoProvider::add_dependency_phases(app, plan, tasks, &task_name, entry_phase)?;
+                return Ok(Some(start.command().to_string()));
+            }
+        }
+        if requested_task.is_some() {
+            bail!(
+                "No task named \"{task_name}\" in deno.json. Available tasks: {}",
+                DenoProvider::format_task_names(tasks.as_ref())
+            );
+        }
+
+        // Deno merges a sibling package.json's scripts into its task runner,
+        // so a bare `start`/`build` script there is reachable via `deno task`
+        if let Some(package_json) = DenoProvider::get_package_json(app)? {
+            if package_json.has_script("start") {
+                return Ok(Some(format!(
+                    "deno{} task start",
+                    DenoProvider::get_lock_flag(app)
+                )));
+            }
         }
 
         // Barring that, just try and start the index file with sane defaults
         match DenoProvider::get_start_file(app)? {
             Some(start_file) => Ok(Some(format!(
-                "deno run --allow-all {}",
+                "deno run --allow-all{}{} {}",
+                DenoProvider::get_lock_flag(app),
+                import_map_flag,
                 start_file
                     .to_slash()
                     .context("Failed to convert start_file to slash_path")?
@@ -106,6 +393,75 @@ impl DenoProvider {
         }
     }
 
+    /// Formats task names for an "available tasks" error, mirroring `deno task`'s own output
+    fn format_task_names(tasks: Option<&DenoTasks>) -> String {
+        match tasks {
+            Some(tasks) if !tasks.is_empty() => {
+                let mut names: Vec<&str> = tasks.keys().map(String::as_str).collect();
+                names.sort_unstable();
+                names.join(", ")
+            }
+            _ => "(none defined)".to_string(),
+        }
+    }
+
+    fn get_deno_tasks(app: &App) -> Result<Option<DenoTasks>> {
+        Ok(DenoProvider::get_deno_json(app)?.and_then(|deno_json| deno_json.tasks))
+    }
+
+    fn get_deno_json(app: &App) -> Result<Option<DenoJson>> {
+        // Prefer deno.jsonc: it's parsed with comment/trailing-comma tolerance,
+        // matching how Deno itself reads it, whereas deno.json is strict JSON.
+        if app.includes_file("deno.jsonc") {
+            Ok(Some(app.read_jsonc("deno.jsonc")?))
+        } else if app.includes_file("deno.json") {
+            Ok(Some(app.read_json("deno.json")?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The path to the `importMap` declared in deno.json, if any
+    fn get_import_map_path(app: &App) -> Result<Option<String>> {
+        Ok(DenoProvider::get_deno_json(app)?.and_then(|deno_json| deno_json.import_map))
+    }
+
+    /// A ` --import-map=<path>` flag suffix for the configured import map
+    fn get_import_map_flag(app: &App) -> Result<String> {
+        Ok(match DenoProvider::get_import_map_path(app)? {
+            Some(import_map) => format!(" --import-map={import_map}"),
+            None => String::new(),
+        })
+    }
+
+    fn get_package_json(app: &App) -> Result<Option<DenoPackageJson>> {
+        if app.includes_file("package.json") {
+            Ok(Some(app.read_json("package.json")?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn get_lockfile_name(app: &App) -> Option<&'static str> {
+        if app.includes_file("deno.lock") {
+            Some("deno.lock")
+        } else {
+            None
+        }
+    }
+
+    /// A ` --lock=deno.lock --frozen` flag suffix when a lockfile is present,
+    /// so the build fails instead of silently drifting from it. Applied to
+    /// the generated `deno task`, `deno cache` and `deno run` commands, not
+    /// just the plain-cache fallback; the `deno install` and `deno vendor`
+    /// commands and a deno.json start task's own command do not carry it.
+    fn get_lock_flag(app: &App) -> &'static str {
+        match DenoProvider::get_lockfile_name(app) {
+            Some(_) => " --lock=deno.lock --frozen",
+            None => "",
+        }
+    }
+
     // Find the first index.{ts,tsx,js,jsx} file to run
     fn get_start_file(app: &App) -> Result<Option<PathBuf>> {
         let matches = app.find_files("**/index.{ts,tsx,js,jsx}")?;
@@ -120,8 +476,91 @@ impl DenoProvider {
 }
 
 mod tests {
+    use super::DenoTask;
     use crate::nixpacks::nix::NIXPACKS_ARCHIVE_LATEST_DENO;
     use crate::{App, DenoProvider, Environment, Provider};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_task_dependency_order() {
+        let tasks = HashMap::from([
+            (
+                "build".to_string(),
+                DenoTask::WithDependencies {
+                    command: "deno run build.ts".to_string(),
+                    dependencies: vec!["codegen".to_string(), "fetch-assets".to_string()],
+                },
+            ),
+            (
+                "codegen".to_string(),
+                DenoTask::Command("deno run codegen.ts".to_string()),
+            ),
+            (
+                "fetch-assets".to_string(),
+                DenoTask::Command("deno run fetch-assets.ts".to_string()),
+            ),
+        ]);
+
+        let order = DenoProvider::topo_sort_task(&tasks, "build").unwrap();
+        assert_eq!(order.last(), Some(&"build".to_string()));
+        assert!(order.iter().position(|t| t == "codegen").unwrap() < order.len() - 1);
+        assert!(order.iter().position(|t| t == "fetch-assets").unwrap() < order.len() - 1);
+    }
+
+    #[test]
+    fn test_task_dependency_cycle_is_rejected() {
+        let tasks = HashMap::from([
+            (
+                "a".to_string(),
+                DenoTask::WithDependencies {
+                    command: "deno run a.ts".to_string(),
+                    dependencies: vec!["b".to_string()],
+                },
+            ),
+            (
+                "b".to_string(),
+                DenoTask::WithDependencies {
+                    command: "deno run b.ts".to_string(),
+                    dependencies: vec!["a".to_string()],
+                },
+            ),
+        ]);
+
+        assert!(DenoProvider::topo_sort_task(&tasks, "a").is_err());
+    }
+
+    #[test]
+    fn test_task_missing_dependency_is_rejected() {
+        let tasks = HashMap::from([(
+            "build".to_string(),
+            DenoTask::WithDependencies {
+                command: "deno run build.ts".to_string(),
+                dependencies: vec!["codegen".to_string()],
+            },
+        )]);
+
+        assert!(DenoProvider::topo_sort_task(&tasks, "build").is_err());
+    }
+
+    #[test]
+    fn test_format_task_names() {
+        let tasks = HashMap::from([
+            (
+                "start".to_string(),
+                DenoTask::Command("deno run main.ts".to_string()),
+            ),
+            (
+                "build".to_string(),
+                DenoTask::Command("deno run build.ts".to_string()),
+            ),
+        ]);
+
+        assert_eq!(
+            DenoProvider::format_task_names(Some(&tasks)),
+            "build, start"
+        );
+        assert_eq!(DenoProvider::format_task_names(None), "(none defined)");
+    }
 
     #[test]
     fn test_deno2() {
@@ -143,4 +582,90 @@ mod tests {
             &NIXPACKS_ARCHIVE_LATEST_DENO.to_string()
         );
     }
+
+    #[test]
+    fn test_package_json_interop_build_and_start() {
+        let deno = DenoProvider {};
+        let plan = deno
+            .get_build_plan(
+                &App::new("examples/deno-npm-interop").unwrap(),
+                &Environment::from_envs(vec![]).unwrap(),
+            )
+            .unwrap()
+            .unwrap();
+
+        let phases = plan.phases.unwrap();
+        let build = phases.get("build").unwrap();
+        assert_eq!(build.cmds.as_ref().unwrap(), &vec!["deno task build".to_string()]);
+        assert_eq!(build.depends_on.as_ref().unwrap(), &vec!["install".to_string()]);
+
+        assert_eq!(plan.start_phase.unwrap().cmd.unwrap(), "deno task start");
+    }
+
+    #[test]
+    fn test_lockfile_flag_reaches_task_phases() {
+        let deno = DenoProvider {};
+        let plan = deno
+            .get_build_plan(
+                &App::new("examples/deno-lockfile").unwrap(),
+                &Environment::from_envs(vec![]).unwrap(),
+            )
+            .unwrap()
+            .unwrap();
+
+        let phases = plan.phases.unwrap();
+        let build = phases.get("deno-task-build").unwrap();
+        assert_eq!(
+            build.cmds.as_ref().unwrap(),
+            &vec!["deno --lock=deno.lock --frozen task build".to_string()]
+        );
+        assert_eq!(build.depends_on.as_ref().unwrap(), &vec!["setup".to_string()]);
+    }
+
+    #[test]
+    fn test_import_map_and_vendor_flow_into_build_and_start() {
+        let deno = DenoProvider {};
+        let plan = deno
+            .get_build_plan(
+                &App::new("examples/deno-import-map-vendor").unwrap(),
+                &Environment::from_envs(vec!["NIXPACKS_DENO_VENDOR=1"]).unwrap(),
+            )
+            .unwrap()
+            .unwrap();
+
+        let phases = plan.phases.unwrap();
+        let vendor = phases.get("vendor").unwrap();
+        assert_eq!(
+            vendor.cmds.as_ref().unwrap(),
+            &vec!["deno vendor --import-map=import_map.json --no-remote index.ts".to_string()]
+        );
+        assert_eq!(vendor.depends_on.as_ref().unwrap(), &vec!["setup".to_string()]);
+
+        let build = phases.get("build").unwrap();
+        assert_eq!(
+            build.cmds.as_ref().unwrap(),
+            &vec!["deno cache --import-map=vendor/import_map.json index.ts".to_string()]
+        );
+        assert_eq!(build.depends_on.as_ref().unwrap(), &vec!["vendor".to_string()]);
+
+        assert_eq!(
+            plan.start_phase.unwrap().cmd.unwrap(),
+            "deno run --allow-all --import-map=vendor/import_map.json index.ts"
+        );
+    }
+
+    #[test]
+    fn test_unknown_requested_build_task_errors() {
+        let deno = DenoProvider {};
+        let err = deno
+            .get_build_plan(
+                &App::new("examples/deno-task-selection-error").unwrap(),
+                &Environment::from_envs(vec!["NIXPACKS_DENO_BUILD_TASK=nonexistent"]).unwrap(),
+            )
+            .unwrap_err();
+
+        let message = err.to_string();
+        assert!(message.contains("nonexistent"));
+        assert!(message.contains("build"));
+    }
 }
@@ -1,23 +1,44 @@
+use std::collections::HashMap;
+
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use toml::Value;
 
-use crate::nixpacks::plan::{
-    phase::{Phase, StartPhase},
-    BuildPlan,
+use crate::nixpacks::{
+    app::App,
+    environment::Environment,
+    plan::{
+        phase::{Phase, StartPhase},
+        BuildPlan,
+    },
 };
 
 use super::Provider;
 
-#[derive(Serialize, Deserialize, Debug)]
-struct PixiTasks {
-    build: Option<Value>,
-    start: Option<Value>,
+/// A pixi.toml `tasks` table: arbitrary task names, each an opaque command
+/// definition (a plain string or pixi's richer `{ cmd, depends-on, ... }` form)
+type PixiTasks = HashMap<String, Value>;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct PixiFeature {
+    #[serde(default)]
+    tasks: PixiTasks,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct PixiEnvironment {
+    #[serde(default)]
+    features: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 struct PixiToml {
+    #[serde(default)]
     tasks: PixiTasks,
+    #[serde(default)]
+    environments: HashMap<String, PixiEnvironment>,
+    #[serde(default, rename = "feature")]
+    features: HashMap<String, PixiFeature>,
 }
 
 pub struct PixiProvider;
@@ -27,37 +48,67 @@ impl Provider for PixiProvider {
         "pixi"
     }
 
-    fn detect(
-        &self,
-        app: &crate::nixpacks::app::App,
-        _env: &crate::nixpacks::environment::Environment,
-    ) -> anyhow::Result<bool> {
+    fn detect(&self, app: &App, _env: &Environment) -> anyhow::Result<bool> {
         Ok(app.has_match("pixi.toml"))
     }
 
     fn get_build_plan(
         &self,
-        app: &crate::nixpacks::app::App,
-        _environment: &crate::nixpacks::environment::Environment,
-    ) -> anyhow::Result<Option<crate::nixpacks::plan::BuildPlan>> {
+        app: &App,
+        environment: &Environment,
+    ) -> anyhow::Result<Option<BuildPlan>> {
         let config = app.read_toml::<PixiToml>("pixi.toml")?;
         let mut plan = BuildPlan::default();
 
+        let pixi_environment = environment.get_config_variable("PIXI_ENVIRONMENT");
+        let tasks = match &pixi_environment {
+            Some(name) => PixiProvider::get_tasks_for_environment(&config, name)?,
+            None => config.tasks.clone(),
+        };
+        let environment_flag = pixi_environment
+            .as_deref()
+            .map(|name| format!(" --environment {name}"))
+            .unwrap_or_default();
+
         let mut setup = Phase::new("setup");
         setup.only_include_files = Some(vec![]);
         setup.add_cmd("curl -fsSL https://pixi.sh/install.sh | bash");
         plan.add_phase(setup);
 
-    let mut install = Phase::install(Some("~/.pixi/bin/pixi install".to_string()));
+        let mut install = Phase::install(Some(format!(
+            "~/.pixi/bin/pixi install{environment_flag}"
+        )));
         install.only_include_files = Some(vec!["pixi.toml".to_string(), "pixi.lock".to_string()]);
         plan.add_phase(install);
 
-        if config.tasks.build.is_some() {
-            plan.add_phase(Phase::build(Some("~/.pixi/bin/pixi run build".to_string())));
+        let requested_build_task = environment.get_config_variable("PIXI_BUILD_TASK");
+        let build_task = requested_build_task
+            .clone()
+            .unwrap_or_else(|| "build".to_string());
+        if tasks.contains_key(&build_task) {
+            plan.add_phase(Phase::build(Some(format!(
+                "~/.pixi/bin/pixi run{environment_flag} {build_task}"
+            ))));
+        } else if requested_build_task.is_some() {
+            return Err(anyhow!(
+                "No task named \"{build_task}\" in pixi.toml. Available tasks: {}",
+                PixiProvider::format_task_names(&tasks)
+            ));
         }
 
-        if config.tasks.start.is_some() {
-            plan.set_start_phase(StartPhase::new("~/.pixi/bin/pixi run start"));
+        let requested_start_task = environment.get_config_variable("PIXI_START_TASK");
+        let start_task = requested_start_task
+            .clone()
+            .unwrap_or_else(|| "start".to_string());
+        if tasks.contains_key(&start_task) {
+            plan.set_start_phase(StartPhase::new(format!(
+                "~/.pixi/bin/pixi run{environment_flag} {start_task}"
+            )));
+        } else if requested_start_task.is_some() {
+            return Err(anyhow!(
+                "No task named \"{start_task}\" in pixi.toml. Available tasks: {}",
+                PixiProvider::format_task_names(&tasks)
+            ));
         } else {
             return Err(anyhow!(
                 "No start task provided; please add one to your pixi.toml."
@@ -67,3 +118,113 @@ impl Provider for PixiProvider {
         Ok(Some(plan))
     }
 }
+
+impl PixiProvider {
+    /// Resolves the task set visible to `environment_name`: the default tasks
+    /// merged with those of every feature the environment pulls in, with
+    /// feature tasks taking precedence, matching pixi's own merge order.
+    fn get_tasks_for_environment(
+        config: &PixiToml,
+        environment_name: &str,
+    ) -> anyhow::Result<PixiTasks> {
+        let environment = config.environments.get(environment_name).ok_or_else(|| {
+            anyhow!(
+                "No environment named \"{environment_name}\" in pixi.toml. Available environments: {}",
+                PixiProvider::format_environment_names(&config.environments)
+            )
+        })?;
+
+        let mut tasks = config.tasks.clone();
+        for feature_name in &environment.features {
+            if let Some(feature) = config.features.get(feature_name) {
+                tasks.extend(feature.tasks.clone());
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    fn format_environment_names(environments: &HashMap<String, PixiEnvironment>) -> String {
+        if environments.is_empty() {
+            return "(none defined)".to_string();
+        }
+
+        let mut names: Vec<&str> = environments.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names.join(", ")
+    }
+
+    /// Formats task names for an "available tasks" error, mirroring `pixi run`'s own output
+    fn format_task_names(tasks: &PixiTasks) -> String {
+        if tasks.is_empty() {
+            return "(none defined)".to_string();
+        }
+
+        let mut names: Vec<&str> = tasks.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str) -> (String, Value) {
+        (name.to_string(), Value::String(format!("echo {name}")))
+    }
+
+    #[test]
+    fn test_get_tasks_for_environment_merges_feature_tasks() {
+        let config = PixiToml {
+            tasks: HashMap::from([task("lint")]),
+            environments: HashMap::from([(
+                "dev".to_string(),
+                PixiEnvironment {
+                    features: vec!["serve".to_string()],
+                },
+            )]),
+            features: HashMap::from([(
+                "serve".to_string(),
+                PixiFeature {
+                    tasks: HashMap::from([task("serve")]),
+                },
+            )]),
+        };
+
+        let tasks = PixiProvider::get_tasks_for_environment(&config, "dev").unwrap();
+        assert!(tasks.contains_key("lint"));
+        assert!(tasks.contains_key("serve"));
+    }
+
+    #[test]
+    fn test_get_tasks_for_environment_unknown_environment_errors() {
+        let config = PixiToml::default();
+        assert!(PixiProvider::get_tasks_for_environment(&config, "dev").is_err());
+    }
+
+    #[test]
+    fn test_format_environment_names() {
+        let environments = HashMap::from([
+            ("dev".to_string(), PixiEnvironment::default()),
+            ("prod".to_string(), PixiEnvironment::default()),
+        ]);
+
+        assert_eq!(
+            PixiProvider::format_environment_names(&environments),
+            "dev, prod"
+        );
+        assert_eq!(
+            PixiProvider::format_environment_names(&HashMap::new()),
+            "(none defined)"
+        );
+    }
+
+    #[test]
+    fn test_format_task_names() {
+        let tasks = HashMap::from([task("build"), task("start")]);
+
+        assert_eq!(PixiProvider::format_task_names(&tasks), "build, start");
+        assert_eq!(PixiProvider::format_task_names(&HashMap::new()), "(none defined)");
+    }
+}